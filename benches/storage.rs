@@ -0,0 +1,152 @@
+use std::hint::black_box;
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use simple_db::storage::file_manager::{BlockId, FileManager};
+use simple_db::storage::page::Page;
+
+const BLOCK_SIZE: usize = 4096;
+const NUM_BLOCKS: u32 = 256;
+const INTS_PER_BLOCK: usize = BLOCK_SIZE / 4;
+
+// ベンチマークごとに空のディレクトリを用意します。
+fn bench_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("simpledb_bench_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// 各ブロックに連番の i32 を詰めたファイルを作成します。
+fn populate(fm: &FileManager, filename: &str) {
+    for block_number in 0..NUM_BLOCKS {
+        let block = fm.append(filename.to_string()).unwrap();
+        let mut page = Page::new(BLOCK_SIZE);
+        for i in 0..INTS_PER_BLOCK {
+            page.write_int((block_number as usize * INTS_PER_BLOCK + i) as i32)
+                .unwrap();
+        }
+        fm.write(&block, &page).unwrap();
+    }
+}
+
+// FileManager のブロック単位の読み書きのスループット（MB/s）
+fn file_manager(c: &mut Criterion) {
+    let dir = bench_dir("file_manager");
+    let fm = FileManager::new(&dir, BLOCK_SIZE);
+    let mapped = FileManager::new(&dir, BLOCK_SIZE).with_mmap();
+    populate(&fm, "data.tbl");
+
+    let mut group = c.benchmark_group("file_manager");
+    group.throughput(Throughput::Bytes(BLOCK_SIZE as u64));
+
+    let mut page = Page::new(BLOCK_SIZE);
+    page.write_bytes(&[7u8; BLOCK_SIZE]).unwrap();
+    let mut n = 0;
+    group.bench_function("write", |b| {
+        b.iter(|| {
+            let block = BlockId::new("data.tbl", n % NUM_BLOCKS);
+            fm.write(&block, &page).unwrap();
+            n += 1;
+        })
+    });
+
+    for (name, manager) in [("read", &fm), ("read_mmap", &mapped)] {
+        let mut n = 0;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let mut page = Page::new(BLOCK_SIZE);
+                manager
+                    .read(&BlockId::new("data.tbl", n % NUM_BLOCKS), &mut page)
+                    .unwrap();
+                n += 1;
+                black_box(page);
+            })
+        });
+    }
+    group.finish();
+
+    drop(mapped);
+    drop(fm);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// Page の write_int / read_int のスループット（ints/s）
+fn page(c: &mut Criterion) {
+    let mut group = c.benchmark_group("page");
+    group.throughput(Throughput::Elements(INTS_PER_BLOCK as u64));
+
+    group.bench_function("write_int", |b| {
+        b.iter(|| {
+            let mut page = Page::new(BLOCK_SIZE);
+            for i in 0..INTS_PER_BLOCK {
+                page.write_int(black_box(i as i32)).unwrap();
+            }
+            black_box(page);
+        })
+    });
+
+    let mut page = Page::new(BLOCK_SIZE);
+    for i in 0..INTS_PER_BLOCK {
+        page.write_int(i as i32).unwrap();
+    }
+    group.bench_function("read_int", |b| {
+        b.iter(|| {
+            page.flip();
+            let mut sum = 0i64;
+            while let Some(value) = page.read_int() {
+                sum += value as i64;
+            }
+            black_box(sum);
+        })
+    });
+    group.finish();
+}
+
+// ファイル全体を先頭から順に読み、各ブロックの値をすべて読み出す全件走査（values/s）
+// TableScan が追加されたら、ここをレコード単位の走査に置き換えます。
+fn sequential_scan(c: &mut Criterion) {
+    let dir = bench_dir("sequential_scan");
+    let fm = FileManager::new(&dir, BLOCK_SIZE);
+    populate(&fm, "scan.tbl");
+
+    let mut group = c.benchmark_group("sequential_scan");
+    group.throughput(Throughput::Elements(
+        NUM_BLOCKS as u64 * INTS_PER_BLOCK as u64,
+    ));
+    group.sample_size(20);
+    for mmap in [false, true] {
+        let manager = if mmap {
+            FileManager::new(&dir, BLOCK_SIZE).with_mmap()
+        } else {
+            FileManager::new(&dir, BLOCK_SIZE)
+        };
+        group.bench_with_input(
+            BenchmarkId::new("full_scan", if mmap { "mmap" } else { "syscall" }),
+            &manager,
+            |b, manager| {
+                b.iter(|| {
+                    let mut sum = 0i64;
+                    for block_number in 0..manager.length("scan.tbl").unwrap() {
+                        let mut page = Page::new(BLOCK_SIZE);
+                        manager
+                            .read(&BlockId::new("scan.tbl", block_number), &mut page)
+                            .unwrap();
+                        page.flip();
+                        while let Some(value) = page.read_int() {
+                            sum += value as i64;
+                        }
+                    }
+                    black_box(sum);
+                })
+            },
+        );
+    }
+    group.finish();
+
+    drop(fm);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+criterion_group!(benches, file_manager, page, sequential_scan);
+criterion_main!(benches);
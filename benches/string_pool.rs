@@ -0,0 +1,84 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use simple_db::storage::page::Page;
+use simple_db::storage::string_pool::StringPool;
+
+const BLOCK_SIZE: usize = 4096;
+
+// メモリ確保の回数を数えるアロケータ
+// バイナリ全体のアロケータを置き換えるため、storage ベンチマークとは別のバイナリにしています。
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// f の実行中に行われたメモリ確保の回数を返します。
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+// カタログのように同じ名前が繰り返し現れるページの文字列読み出し
+// read_str は読み出すたびに String を確保しますが、read_interned_str は
+// プールにある文字列を共有するため、2 回目以降は確保を行いません。
+fn string_pool(c: &mut Criterion) {
+    const NAMES: [&str; 8] = [
+        "tblcat", "fldcat", "student", "dept", "course", "section", "enroll", "sname",
+    ];
+    let mut page = Page::new(BLOCK_SIZE);
+    let mut count = 0;
+    while page.write_str(NAMES[count % NAMES.len()]).is_ok() {
+        count += 1;
+    }
+
+    let read_all = |page: &mut Page| {
+        page.flip();
+        while let Some(s) = page.read_str() {
+            black_box(s);
+        }
+    };
+    let pool = StringPool::new();
+    let read_all_interned = |page: &mut Page| {
+        page.flip();
+        while let Some(s) = page.read_interned_str(&pool) {
+            black_box(s);
+        }
+    };
+
+    // 計測の前に、1 ページ分を読み出したときの確保回数を表示します。
+    read_all_interned(&mut page);
+    let plain = allocations_during(|| read_all(&mut page));
+    let interned = allocations_during(|| read_all_interned(&mut page));
+    println!(
+        "string_pool: {} strings per page, allocations: read_str = {}, read_interned_str = {}",
+        count, plain, interned
+    );
+
+    let mut group = c.benchmark_group("string_pool");
+    group.throughput(Throughput::Elements(count as u64));
+    group.bench_function("read_str", |b| b.iter(|| read_all(&mut page)));
+    group.bench_function("read_interned_str", |b| {
+        b.iter(|| read_all_interned(&mut page))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, string_pool);
+criterion_main!(benches);
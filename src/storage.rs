@@ -1,3 +1,7 @@
 pub mod file_manager;
 pub mod block_id;
-pub mod page;
\ No newline at end of file
+pub mod page;
+pub mod observer;
+pub mod path_strategy;
+pub mod string_pool;
+pub mod decimal;
\ No newline at end of file
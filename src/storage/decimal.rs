@@ -0,0 +1,80 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// 固定小数点の 10 進数
+/// - 値は unscaled / 10^scale を表します（例: 12.34 は unscaled = 1234, scale = 2）。
+/// - 浮動小数点数と異なり、金額などを誤差なく表現できます。
+/// - 比較は scale の違いを考慮して値そのもので行うため、1.2 と 1.20 は等しくなります。
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    unscaled: i64,
+    scale: u8,
+}
+
+impl Decimal {
+    /// scale の上限（i64 で表現できる 10 進数の桁数）
+    pub const MAX_SCALE: u8 = 18;
+
+    /// 新しい Decimal を作成します。scale が MAX_SCALE を超える場合は None を返します。
+    pub fn new(unscaled: i64, scale: u8) -> Option<Decimal> {
+        if scale > Self::MAX_SCALE {
+            return None;
+        }
+        Some(Decimal { unscaled, scale })
+    }
+
+    /// スケールする前の整数値を返します。
+    pub fn unscaled(&self) -> i64 {
+        self.unscaled
+    }
+
+    /// 小数点以下の桁数を返します。
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    // scale 桁にそろえた整数値を返します（オーバーフローしないよう i128 で計算）。
+    fn rescaled(&self, scale: u8) -> i128 {
+        self.unscaled as i128 * 10i128.pow((scale - self.scale) as u32)
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescaled(scale).cmp(&other.rescaled(scale))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.scale == 0 {
+            return write!(f, "{}", self.unscaled);
+        }
+        let divisor = 10u64.pow(self.scale as u32);
+        let abs = self.unscaled.unsigned_abs();
+        let sign = if self.unscaled < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{:0width$}",
+            sign,
+            abs / divisor,
+            abs % divisor,
+            width = self.scale as usize
+        )
+    }
+}
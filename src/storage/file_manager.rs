@@ -1,8 +1,12 @@
+use std::collections::{HashMap, VecDeque};
 use std::fs::OpenOptions;
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use memmap2::Mmap;
+use crate::storage::observer::{DbObserver, NoopObserver};
 use crate::storage::page::Page;
+use crate::storage::path_strategy::{FlatPathStrategy, PathStrategy};
 
 #[derive(Debug, Clone)]
 pub struct BlockId {
@@ -24,10 +28,27 @@ impl BlockId {
 /// FileManager クラス
 /// - db_directory と block_size をプライベート変数に持ちます。
 /// - 同時実行を防ぐため、内部に Mutex を保持します。
+///   Mutex は書き込みバッファ（まだファイルに書き出していないブロック）とメモリマップも保護します。
+/// - ブロックの読み書きは observer に通知されます。
+/// - ファイル名から実際のパスへの変換は path_strategy に委ねます。
+/// - write_buffer_capacity が 1 以上のとき、書き込みは書き込みバッファに溜めてまとめて書き出します。
 pub struct FileManager {
     db_directory: PathBuf,
     block_size: usize,
-    lock: Mutex<()>,
+    lock: Mutex<Buffers>,
+    observer: Arc<dyn DbObserver>,
+    path_strategy: Box<dyn PathStrategy>,
+    write_buffer_capacity: usize,
+    use_mmap: bool,
+}
+
+// FileManager の Mutex で保護する状態
+#[derive(Default)]
+struct Buffers {
+    // 書き込みバッファ: まだファイルに書き出していないブロックの最新の内容（最初に書き込まれたものが先頭）
+    pending: VecDeque<(BlockId, Vec<u8>)>,
+    // 読み込みに使うファイルごとのメモリマップ
+    mappings: HashMap<PathBuf, Mmap>,
 }
 
 impl FileManager {
@@ -38,62 +59,173 @@ impl FileManager {
         FileManager {
             db_directory: db_directory.into(),
             block_size,
-            lock: Mutex::new(()),
+            lock: Mutex::new(Buffers::default()),
+            observer: Arc::new(NoopObserver),
+            path_strategy: Box::new(FlatPathStrategy),
+            write_buffer_capacity: 0,
+            use_mmap: false,
         }
     }
+
+    /// ブロックの読み書きを通知する observer を設定します。
+    pub fn with_observer(mut self, observer: Arc<dyn DbObserver>) -> FileManager {
+        self.observer = observer;
+        self
+    }
+
+    /// ファイルの配置に使う PathStrategy を差し替えます。
+    /// 既存のファイルは移動されないため、データベースを作成する時点で指定してください。
+    pub fn with_path_strategy(mut self, path_strategy: Box<dyn PathStrategy>) -> FileManager {
+        self.path_strategy = path_strategy;
+        self
+    }
+
+    /// 最大 capacity 個のブロックの書き込みをメモリ上に保持する書き込みバッファを有効にします。
+    /// 同じブロックへの書き込みはバッファ上で上書きされ、ファイルへの書き込みは
+    /// バッファから溢れたときか flush を呼んだときの一度だけになります。
+    /// ファイルへは、各ブロックが最初にバッファに入った順に書き出されます（WAL の順序を保つため）。
+    /// 書き込みの永続性が必要な時点（コミットなど）では必ず flush を呼んでください。
+    /// capacity が 0 の場合（デフォルト）は、書き込みのたびにファイルへ書き込みます。
+    pub fn with_write_buffer(mut self, capacity: usize) -> FileManager {
+        self.write_buffer_capacity = capacity;
+        self
+    }
+
+    /// 読み込みをメモリマップ経由で行うようにします（書き込みは従来どおりファイルに対して行います）。
+    /// 読み込みのたびにシステムコールを発行しないため、読み込みの多い処理で有効です。
+    /// マップ中のファイルを他のプロセスが切り詰めると未定義動作になるため、
+    /// データベースのファイルを FileManager 以外から変更しない場合にのみ使用してください。
+    pub fn with_mmap(mut self) -> FileManager {
+        self.use_mmap = true;
+        self
+    }
     
     /// 指定された BlockId のブロックをファイルから読み込み、Page にセットします。
     /// このメソッドは Mutex によって排他的に実行されるため、
     /// 複数のスレッドで同時に呼び出されても一度に一つしか実行されません。
+    /// Page の位置は読み込みの前後で 0 にリセットされるため、同じ Page を繰り返し使えます。
     pub fn read(&self, block: &BlockId, page: &mut Page) -> std::io::Result<()> {
         // Mutex をロックして排他制御
-        let _guard = self.lock.lock().unwrap();
+        let mut buffers = self.lock.lock().unwrap();
 
-        // db_directory と BlockId.filename を結合してファイルのフルパスを作成
-        let mut path = self.db_directory.clone();
-        path.push(&block.filename);
-        
-        // ファイルをオープン
-        let mut file = std::fs::File::open(&path)?;
-        
-        // ブロックの先頭オフセットを計算 (block_size * block.number)
-        let offset = (self.block_size as u64) * (block.number as u64);
-        file.seek(SeekFrom::Start(offset))?;
-        
         // block_size バイト分のデータを読み込む
+        // （書き込みバッファにより新しい内容があれば、そちらが優先される）
         let mut buffer = vec![0u8; self.block_size];
-        let n = file.read(&mut buffer)?;
+        let n = self.read_block(&mut buffers, block, &mut buffer)?;
         if n != self.block_size {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::UnexpectedEof,
                 "Could not read full block",
             ));
         }
+
+        // 読み込んだデータを Page の先頭からセットし、読み出し位置を 0 に戻す
+        page.flip();
+        page.write_bytes(buffer.as_slice())?;
+        page.flip();
+        self.observer.block_read(block);
         
-        // 読み込んだデータを Page にセット（読み出し位置は 0 にリセット）
-        page.write_bytes(buffer.as_slice());
-        
-        // _guard はスコープ終了時に自動的に解放されます。
+        // buffers（ロック）はスコープ終了時に自動的に解放されます。
         Ok(())
     }
 
+    /// read_partial(block, page)
+    /// ブロックを最大 block_size バイトまで読み込み、実際に読み込めたバイト数を返します。
+    /// ファイル末尾のブロックが block_size に満たない場合でもエラーにせず、
+    /// Page の残りの部分は 0 で埋めます。Page の位置は read と同様に 0 にリセットされます。
+    pub fn read_partial(&self, block: &BlockId, page: &mut Page) -> std::io::Result<usize> {
+        // 排他制御
+        let mut buffers = self.lock.lock().unwrap();
+
+        let mut buffer = vec![0u8; self.block_size];
+        let n = self.read_block(&mut buffers, block, &mut buffer)?;
+
+        // 読み込めなかった部分は 0 のまま Page の先頭からセットする
+        page.flip();
+        page.write_bytes(buffer.as_slice())?;
+        page.flip();
+        self.observer.block_read(block);
+        Ok(n)
+    }
+
+    /// read_into(block, buf)
+    /// Page を介さずに、呼び出し側が用意した長さ block_size のスライスへブロックを読み込み、
+    /// 実際に読み込めたバイト数を返します。読み込めなかった部分は 0 で埋めます。
+    /// スライスの長さが block_size と異なる場合はエラーを返します。
+    pub fn read_into(&self, block: &BlockId, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.len() != self.block_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "buffer length {} does not match block size {}",
+                    buf.len(),
+                    self.block_size
+                ),
+            ));
+        }
+
+        // 排他制御
+        let mut buffers = self.lock.lock().unwrap();
+        let n = self.read_block(&mut buffers, block, buf)?;
+        self.observer.block_read(block);
+        Ok(n)
+    }
+
     /// write(block, page)
     /// Page の内容を、BlockId が示すブロック位置に書き込みます。
+    /// 書き込みバッファが有効な場合は、バッファに保持するだけでファイルには書き込みません。
+    /// 存在しないファイルへの書き込みや、溢れたブロックの書き出しに失敗した場合は、
+    /// バッファを変更せずにエラーを返します。
     pub fn write(&self, block: &BlockId, page: &Page) -> std::io::Result<()> {
         // 排他制御
-        let _guard = self.lock.lock().unwrap();
+        let mut buffers = self.lock.lock().unwrap();
+        let pending = &mut buffers.pending;
 
-        // db_directory と BlockId.filename を結合してファイルパスを作成
-        let mut path = self.db_directory.clone();
-        path.push(&block.filename);
-        
-        // 書き込みモードでファイルをオープン（ファイルは既存のものとする）
-        let mut file = OpenOptions::new().write(true).open(&path)?;
-        let offset = (self.block_size as u64) * (block.number as u64);
-        file.seek(SeekFrom::Start(offset))?;
-        file.write(&page.bytebuffer())?;
+        if self.write_buffer_capacity == 0 {
+            return self.write_block(block, page.bytebuffer());
+        }
+
+        // すでにバッファにあるブロックは、最初に書き込まれた位置のまま内容だけを更新する
+        if let Some((_, bytes)) = pending.iter_mut().find(|(b, _)| same_block(b, block)) {
+            bytes.clone_from(page.bytebuffer());
+            return Ok(());
+        }
+
+        // 書き出せないブロックはバッファに入れない
+        std::fs::metadata(self.file_path(&block.filename))?;
+        // バッファが一杯なら、最も古いブロックを書き出してから追加する
+        if pending.len() >= self.write_buffer_capacity {
+            if let Some((oldest, bytes)) = pending.front() {
+                self.write_block(oldest, bytes)?;
+            }
+            pending.pop_front();
+        }
+        pending.push_back((block.clone(), page.bytebuffer().clone()));
         Ok(())
     }
+
+    /// flush()
+    /// 書き込みバッファに保持しているすべてのブロックをファイルに書き出します。
+    /// 書き出せないブロックがあっても残りのブロックの書き出しを続け、最初のエラーを返します。
+    /// 書き出せなかったブロックはバッファに残ります。
+    pub fn flush(&self) -> std::io::Result<()> {
+        // 排他制御
+        let mut buffers = self.lock.lock().unwrap();
+        let pending = &mut buffers.pending;
+
+        let mut first_error = None;
+        pending.retain(|(block, bytes)| match self.write_block(block, bytes) {
+            Ok(()) => false,
+            Err(e) => {
+                first_error.get_or_insert(e);
+                true
+            }
+        });
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
     
     /// append(filename)
     /// 指定されたファイル名に対して、新たなブロックを確保（ファイルサイズを block_size 分延長）し、
@@ -101,15 +233,92 @@ impl FileManager {
     pub fn append(&self, filename: String) -> std::io::Result<BlockId> {
         // 排他制御
         let _guard = self.lock.lock().unwrap();
-        
-        let mut path = self.db_directory.clone();
-        path.push(&filename);
+        self.append_block(filename)
+    }
+
+    /// allocate(filename)
+    /// 空きブロックリストにブロックが登録されていればそれを再利用し、
+    /// 空であれば append と同様にファイルを延長して新たなブロックを確保します。
+    /// 再利用するブロックは、以前の内容が残らないよう 0 で埋めてから返します。
+    pub fn allocate(&self, filename: String) -> std::io::Result<BlockId> {
+        // 排他制御
+        let mut buffers = self.lock.lock().unwrap();
+        let pending = &mut buffers.pending;
+
+        let mut free_blocks = self.read_free_list(&filename)?;
+        match free_blocks.pop() {
+            Some(block_number) => {
+                // 先に空きブロックリストから取り除いて保存する
+                // （途中で異常終了しても、同じブロックが二重に割り当てられることはない）
+                self.write_free_list(&filename, &free_blocks)?;
+                let block = BlockId::new(filename, block_number);
+                // 書き込みバッファに残っている古い内容も捨てる
+                pending.retain(|(b, _)| !same_block(b, &block));
+                self.write_block(&block, &vec![0u8; self.block_size])?;
+                Ok(block)
+            }
+            None => self.append_block(filename),
+        }
+    }
+
+    /// free_block(block)
+    /// 不要になったブロックを空きブロックリストに登録し、allocate で再利用できるようにします。
+    /// 空きブロックリストは `<filename>.free` というサイドファイルに永続化されます。
+    /// ファイルの末尾を超えるブロックを指定した場合はエラーを返します。
+    pub fn free_block(&self, block: &BlockId) -> std::io::Result<()> {
+        // 排他制御
+        let _guard = self.lock.lock().unwrap();
+
+        let filename = block.filename.to_string_lossy();
+        let num_blocks = self.block_count(&filename)?;
+        if block.number >= num_blocks {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "block {} is beyond the end of {} ({} blocks)",
+                    block.number, filename, num_blocks
+                ),
+            ));
+        }
+        let mut free_blocks = self.read_free_list(&filename)?;
+        if !free_blocks.contains(&block.number) {
+            free_blocks.push(block.number);
+        }
+        self.write_free_list(&filename, &free_blocks)
+    }
+
+    /// length(filename)
+    /// 指定されたファイルのブロック数を返します。ファイルが存在しなければ 0 を返します。
+    pub fn length(&self, filename: &str) -> std::io::Result<u32> {
+        // 排他制御
+        let _guard = self.lock.lock().unwrap();
+        self.block_count(filename)
+    }
+
+    // ロックを取得済みの状態で呼び出す length の本体
+    fn block_count(&self, filename: &str) -> std::io::Result<u32> {
+        let path = self.file_path(Path::new(filename));
+        match std::fs::metadata(&path) {
+            Ok(metadata) => Ok((metadata.len() / (self.block_size as u64)) as u32),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    // ロックを取得済みの状態で呼び出す append の本体
+    fn append_block(&self, filename: String) -> std::io::Result<BlockId> {
+        let path = self.file_path(Path::new(&filename));
+        // PathStrategy によってはサブディレクトリが必要になる
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
         
         // ファイルを読み書き可能な状態でオープン（存在しなければ作成）
-        let mut file = OpenOptions::new()
+        let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
+            .truncate(false)
             .open(&path)?;
         
         // 現在のファイルサイズを取得
@@ -121,6 +330,470 @@ impl FileManager {
         file.set_len(new_len)?;
         
         // 確保したブロックの BlockId を返す
-        Ok(BlockId::new(filename, block_number))
-    }    
+        let block = BlockId::new(filename, block_number);
+        self.observer.block_appended(&block);
+        Ok(block)
+    }
+
+    // ロックを取得済みの状態で、ブロックを buf に読み込み、読み込めたバイト数を返します。
+    // buf の長さは block_size であるものとし、読み込めなかった部分は 0 で埋めます。
+    fn read_block(
+        &self,
+        buffers: &mut Buffers,
+        block: &BlockId,
+        buf: &mut [u8],
+    ) -> std::io::Result<usize> {
+        let path = self.file_path(&block.filename);
+        let offset = (self.block_size as u64) * (block.number as u64);
+        let n = if self.use_mmap {
+            read_mapped(&mut buffers.mappings, &path, offset as usize, buf)?
+        } else {
+            let mut file = std::fs::File::open(&path)?;
+            file.seek(SeekFrom::Start(offset))?;
+
+            // ファイル末尾に達するか block_size バイト読み込むまで読み続ける
+            let mut n = 0;
+            while n < buf.len() {
+                match file.read(&mut buf[n..])? {
+                    0 => break,
+                    read => n += read,
+                }
+            }
+            n
+        };
+        buf[n..].fill(0);
+
+        // 書き込みバッファにより新しい内容があれば、そちらを優先する
+        apply_pending(&buffers.pending, block, buf);
+        Ok(n)
+    }
+
+    // ロックを取得済みの状態で、bytes をブロックの位置に書き込みます。
+    fn write_block(&self, block: &BlockId, bytes: &[u8]) -> std::io::Result<()> {
+        // db_directory と BlockId.filename からファイルパスを作成
+        let path = self.file_path(&block.filename);
+
+        // 書き込みモードでファイルをオープン（ファイルは既存のものとする）
+        let mut file = OpenOptions::new().write(true).open(&path)?;
+        let offset = (self.block_size as u64) * (block.number as u64);
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(bytes)?;
+        self.observer.block_written(block);
+        Ok(())
+    }
+
+    // PathStrategy に従ってファイルのフルパスを求めます。
+    fn file_path(&self, filename: &Path) -> PathBuf {
+        self.path_strategy.resolve(&self.db_directory, filename)
+    }
+
+    // 空きブロックリストのサイドファイルのパス（データファイルと同じディレクトリに置く）
+    fn free_list_path(&self, filename: &str) -> PathBuf {
+        let mut path = self.file_path(Path::new(filename));
+        path.as_mut_os_string().push(".free");
+        path
+    }
+
+    // 空きブロックリストを読み込みます（ブロック番号を 4 バイトのビッグエンディアンで並べた形式）。
+    fn read_free_list(&self, filename: &str) -> std::io::Result<Vec<u32>> {
+        let bytes = match std::fs::read(self.free_list_path(filename)) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect())
+    }
+
+    // 空きブロックリストをサイドファイルに書き戻します。
+    // 一時ファイルに書き込んでから置き換えるため、書き込み途中のリストが残ることはありません。
+    fn write_free_list(&self, filename: &str, free_blocks: &[u32]) -> std::io::Result<()> {
+        let bytes: Vec<u8> = free_blocks.iter().flat_map(|n| n.to_be_bytes()).collect();
+        let path = self.free_list_path(filename);
+        let mut tmp_path = path.clone();
+        tmp_path.as_mut_os_string().push(".tmp");
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, &path)
+    }
+}
+
+impl Drop for FileManager {
+    // 書き込みバッファに残っている内容を可能な限り書き出します。
+    // エラーは報告できないため、確実に永続化したい場合は明示的に flush を呼んでください。
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+// メモリマップから offset 以降を buf にコピーし、コピーしたバイト数を返します。
+// マップがまだないか、ファイルが伸びてマップが足りない場合はマップし直します。
+fn read_mapped(
+    mappings: &mut HashMap<PathBuf, Mmap>,
+    path: &Path,
+    offset: usize,
+    buf: &mut [u8],
+) -> std::io::Result<usize> {
+    let stale = match mappings.get(path) {
+        Some(mmap) => mmap.len() < offset + buf.len(),
+        None => true,
+    };
+    if stale {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: マップしたファイルは FileManager だけが書き込み・延長を行い、
+        // 切り詰めることはない（with_mmap のドキュメントを参照）。
+        let mmap = unsafe { Mmap::map(&file)? };
+        mappings.insert(path.to_path_buf(), mmap);
+    }
+
+    let mmap = &mappings[path];
+    let start = offset.min(mmap.len());
+    let n = (mmap.len() - start).min(buf.len());
+    buf[..n].copy_from_slice(&mmap[start..start + n]);
+    Ok(n)
+}
+
+// 書き込みバッファにあるブロックの内容で buffer を上書きします。
+fn apply_pending(pending: &VecDeque<(BlockId, Vec<u8>)>, block: &BlockId, buffer: &mut [u8]) {
+    if let Some((_, bytes)) = pending.iter().find(|(b, _)| same_block(b, block)) {
+        let n = bytes.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&bytes[..n]);
+    }
+}
+
+// 2 つの BlockId が同じブロックを指しているかどうか
+fn same_block(a: &BlockId, b: &BlockId) -> bool {
+    a.number == b.number && a.filename == b.filename
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::file_manager::{BlockId, FileManager};
+    use crate::storage::observer::DbObserver;
+    use crate::storage::page::Page;
+    use crate::storage::path_strategy::ShardedPathStrategy;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // テストごとに空のディレクトリを用意します。
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("simpledb_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn allocate_reuses_freed_block() {
+        let dir = test_dir("free_list");
+        let fm = FileManager::new(&dir, 64);
+
+        fm.append("t.tbl".to_string()).unwrap();
+        let last = fm.append("t.tbl".to_string()).unwrap();
+        assert_eq!(fm.length("t.tbl").unwrap(), 2);
+
+        let mut page = Page::new(64);
+        page.write_bytes(&[9u8; 64]).unwrap();
+        fm.write(&last, &page).unwrap();
+
+        fm.free_block(&last).unwrap();
+        let reused = fm.allocate("t.tbl".to_string()).unwrap();
+        assert_eq!(reused.number, last.number);
+        assert_eq!(fm.length("t.tbl").unwrap(), 2);
+
+        // 再利用したブロックは 0 で埋められています。
+        let mut page = Page::new(64);
+        fm.read(&reused, &mut page).unwrap();
+        assert_eq!(page.bytebuffer(), &vec![0u8; 64]);
+
+        // 空きブロックがなくなれば、ファイルを延長します。
+        let appended = fm.allocate("t.tbl".to_string()).unwrap();
+        assert_eq!(appended.number, 2);
+        assert_eq!(fm.length("t.tbl").unwrap(), 3);
+
+        // ファイルの末尾を超えるブロックは解放できず、ファイルも伸びません。
+        assert!(fm.free_block(&BlockId::new("t.tbl", 3)).is_err());
+        assert!(fm.free_block(&BlockId::new("missing.tbl", 0)).is_err());
+        assert_eq!(fm.allocate("t.tbl".to_string()).unwrap().number, 3);
+        assert_eq!(fm.length("t.tbl").unwrap(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn free_list_persists_across_managers() {
+        let dir = test_dir("free_list_persist");
+        let fm = FileManager::new(&dir, 64);
+        let block = fm.append("t.tbl".to_string()).unwrap();
+        fm.free_block(&block).unwrap();
+        drop(fm);
+
+        let fm = FileManager::new(&dir, 64);
+        assert_eq!(fm.allocate("t.tbl".to_string()).unwrap().number, block.number);
+        assert_eq!(fm.length("t.tbl").unwrap(), 1);
+        // 割り当てたブロックはサイドファイルからも取り除かれ、一時ファイルは残らない
+        assert!(std::fs::read(dir.join("t.tbl.free")).unwrap().is_empty());
+        assert!(!dir.join("t.tbl.free.tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[derive(Default)]
+    struct CountingObserver {
+        reads: AtomicUsize,
+        writes: AtomicUsize,
+        appends: AtomicUsize,
+    }
+
+    impl DbObserver for CountingObserver {
+        fn block_read(&self, _block: &BlockId) {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn block_written(&self, _block: &BlockId) {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn block_appended(&self, _block: &BlockId) {
+            self.appends.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn observer_counts_block_events() {
+        let dir = test_dir("observer");
+        let observer = Arc::new(CountingObserver::default());
+        let fm = FileManager::new(&dir, 16).with_observer(observer.clone());
+
+        let mut page = Page::new(16);
+        page.write_bytes(&[1u8; 16]).unwrap();
+        for _ in 0..3 {
+            let block = fm.append("t.tbl".to_string()).unwrap();
+            fm.write(&block, &page).unwrap();
+        }
+        let mut page = Page::new(16);
+        fm.read(&BlockId::new("t.tbl", 1), &mut page).unwrap();
+        // ファイル末尾を超えるブロックの読み込みは失敗し、通知もされない
+        let mut page = Page::new(16);
+        assert!(fm.read(&BlockId::new("t.tbl", 3), &mut page).is_err());
+
+        assert_eq!(observer.appends.load(Ordering::SeqCst), 3);
+        assert_eq!(observer.writes.load(Ordering::SeqCst), 3);
+        assert_eq!(observer.reads.load(Ordering::SeqCst), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_partial_zero_fills_final_block() {
+        let dir = test_dir("read_partial");
+        std::fs::write(dir.join("ext.dat"), vec![7u8; 100]).unwrap();
+        let fm = FileManager::new(&dir, 64);
+
+        let mut page = Page::new(64);
+        assert!(fm.read(&BlockId::new("ext.dat", 1), &mut page).is_err());
+
+        let mut page = Page::new(64);
+        let n = fm.read_partial(&BlockId::new("ext.dat", 1), &mut page).unwrap();
+        assert_eq!(n, 36);
+        assert_eq!(page.bytebuffer().len(), 64);
+        assert!(page.bytebuffer()[..36].iter().all(|&b| b == 7));
+        assert!(page.bytebuffer()[36..].iter().all(|&b| b == 0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sharded_strategy_places_file_in_subdirectory() {
+        let dir = test_dir("sharded");
+        let fm = FileManager::new(&dir, 16).with_path_strategy(Box::new(ShardedPathStrategy));
+
+        let block = fm.append("student.tbl".to_string()).unwrap();
+        let mut page = Page::new(16);
+        page.write_bytes(&[3u8; 16]).unwrap();
+        fm.write(&block, &page).unwrap();
+        assert!(dir.join("student").join("student.tbl").is_file());
+        assert!(!dir.join("student.tbl").exists());
+
+        // 書き込みに使った Page をそのまま読み込みに使い回せる
+        fm.read(&block, &mut page).unwrap();
+        assert_eq!(page.bytebuffer(), &vec![3u8; 16]);
+        assert_eq!(page.read_int(), Some(0x03030303));
+        fm.read(&block, &mut page).unwrap();
+        assert_eq!(page.read_int(), Some(0x03030303));
+        assert_eq!(fm.length("student.tbl").unwrap(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_buffer_coalesces_repeated_writes() {
+        let dir = test_dir("write_buffer");
+        let observer = Arc::new(CountingObserver::default());
+        let fm = FileManager::new(&dir, 4).with_observer(observer.clone()).with_write_buffer(4);
+        let block = fm.append("counter.tbl".to_string()).unwrap();
+
+        for i in 0..100 {
+            let mut page = Page::new(4);
+            page.write_int(i).unwrap();
+            fm.write(&block, &page).unwrap();
+        }
+        assert_eq!(observer.writes.load(Ordering::SeqCst), 0);
+
+        // 書き出し前でも、読み込みには最新の内容が見える
+        let mut page = Page::new(4);
+        fm.read(&block, &mut page).unwrap();
+        page.flip();
+        assert_eq!(page.read_int(), Some(99));
+
+        fm.flush().unwrap();
+        assert_eq!(observer.writes.load(Ordering::SeqCst), 1);
+        assert_eq!(std::fs::read(dir.join("counter.tbl")).unwrap(), 99i32.to_be_bytes());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_buffer_spills_oldest_block() {
+        let dir = test_dir("write_buffer_spill");
+        let observer = Arc::new(CountingObserver::default());
+        let fm = FileManager::new(&dir, 4).with_observer(observer.clone()).with_write_buffer(2);
+
+        let mut page = Page::new(4);
+        page.write_int(1).unwrap();
+        for _ in 0..3 {
+            let block = fm.append("t.tbl".to_string()).unwrap();
+            fm.write(&block, &page).unwrap();
+        }
+        // 3 ブロック目の書き込みで、最も古い 1 ブロック目が書き出される
+        assert_eq!(observer.writes.load(Ordering::SeqCst), 1);
+
+        // drop 時に残りの 2 ブロックも書き出される
+        drop(fm);
+        assert_eq!(observer.writes.load(Ordering::SeqCst), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_into_fills_caller_buffer() {
+        let dir = test_dir("read_into");
+        let fm = FileManager::new(&dir, 8);
+        fm.append("t.tbl".to_string()).unwrap();
+        let block = fm.append("t.tbl".to_string()).unwrap();
+        let mut page = Page::new(8);
+        page.write_bytes(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        fm.write(&block, &page).unwrap();
+
+        let mut buf = [0xffu8; 8];
+        assert_eq!(fm.read_into(&block, &mut buf).unwrap(), 8);
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut wrong_size = vec![0u8; 4];
+        assert!(fm.read_into(&block, &mut wrong_size).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn mmap_reads_match_syscall_reads() {
+        let dir = test_dir("mmap");
+        let fm = FileManager::new(&dir, 8);
+        let mapped = FileManager::new(&dir, 8).with_mmap();
+
+        let first = fm.append("t.tbl".to_string()).unwrap();
+        let mut page = Page::new(8);
+        page.write_bytes(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        fm.write(&first, &page).unwrap();
+
+        let mut expected = Page::new(8);
+        let mut actual = Page::new(8);
+        fm.read(&first, &mut expected).unwrap();
+        mapped.read(&first, &mut actual).unwrap();
+        assert_eq!(actual.bytebuffer(), expected.bytebuffer());
+
+        // ファイルが伸びた後のブロックも、マップし直して読み込める
+        let second = fm.append("t.tbl".to_string()).unwrap();
+        let mut page = Page::new(8);
+        page.write_bytes(&[8, 7, 6, 5, 4, 3, 2, 1]).unwrap();
+        fm.write(&second, &page).unwrap();
+
+        let mut buf = [0u8; 8];
+        mapped.read_into(&second, &mut buf).unwrap();
+        assert_eq!(buf, [8, 7, 6, 5, 4, 3, 2, 1]);
+
+        // 既存のマップ上のブロックへの書き込みも見える
+        mapped.write(&first, &page).unwrap();
+        mapped.read_into(&first, &mut buf).unwrap();
+        assert_eq!(buf, [8, 7, 6, 5, 4, 3, 2, 1]);
+
+        // ファイル末尾を超えるブロックは syscall の経路と同じくエラーになる
+        let mut page = Page::new(8);
+        assert!(mapped.read(&BlockId::new("t.tbl", 5), &mut page).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn write_buffer_keeps_first_write_order() {
+        let dir = test_dir("write_buffer_order");
+        let fm = FileManager::new(&dir, 4).with_write_buffer(2);
+        let log = fm.append("log.tbl".to_string()).unwrap();
+        let data = fm.append("data.tbl".to_string()).unwrap();
+        let other = fm.append("other.tbl".to_string()).unwrap();
+
+        let mut page = Page::new(4);
+        page.write_int(1).unwrap();
+        fm.write(&log, &page).unwrap();
+        fm.write(&data, &page).unwrap();
+        let mut page = Page::new(4);
+        page.write_int(2).unwrap();
+        fm.write(&log, &page).unwrap();
+
+        // 溢れたときは、後から書き直されたログのブロックがデータより先に書き出される
+        fm.write(&other, &page).unwrap();
+        assert_eq!(std::fs::read(dir.join("log.tbl")).unwrap(), 2i32.to_be_bytes());
+        assert_eq!(std::fs::read(dir.join("data.tbl")).unwrap(), [0u8; 4]);
+
+        drop(fm);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_writes_do_not_grow_write_buffer() {
+        let dir = test_dir("write_buffer_failed");
+        let fm = FileManager::new(&dir, 4).with_write_buffer(2);
+        let mut page = Page::new(4);
+        page.write_int(42).unwrap();
+
+        // 存在しないファイルへの書き込みはバッファに入らない
+        assert!(fm.write(&BlockId::new("missing.tbl", 0), &page).is_err());
+
+        let a = fm.append("a.tbl".to_string()).unwrap();
+        let b = fm.append("b.tbl".to_string()).unwrap();
+        let c = fm.append("c.tbl".to_string()).unwrap();
+        fm.write(&a, &page).unwrap();
+        fm.write(&b, &page).unwrap();
+
+        // 溢れた a.tbl の書き出しに失敗すると、c.tbl の書き込みはバッファに入らない
+        std::fs::remove_file(dir.join("a.tbl")).unwrap();
+        assert!(fm.write(&c, &page).is_err());
+        assert_eq!(std::fs::read(dir.join("c.tbl")).unwrap(), [0u8; 4]);
+
+        // flush は失敗したブロックの後ろのブロックも書き出してからエラーを返す
+        assert!(fm.flush().is_err());
+        assert_eq!(std::fs::read(dir.join("b.tbl")).unwrap(), 42i32.to_be_bytes());
+
+        // 書き出せなかったブロックはバッファに残り、後から書き出せる
+        fm.append("a.tbl".to_string()).unwrap();
+        fm.flush().unwrap();
+        assert_eq!(std::fs::read(dir.join("a.tbl")).unwrap(), 42i32.to_be_bytes());
+
+        drop(fm);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
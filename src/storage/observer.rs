@@ -0,0 +1,20 @@
+use crate::storage::file_manager::BlockId;
+
+/// ストレージ層のイベントを受け取るためのフック
+/// - すべてのメソッドは何もしないデフォルト実装を持つため、必要なものだけを実装できます。
+/// - Prometheus や tracing などへの接続は利用者側で行い、このクレート自体はそれらに依存しません。
+pub trait DbObserver: Send + Sync {
+    /// ブロックがファイルから読み込まれたときに呼ばれます。
+    fn block_read(&self, _block: &BlockId) {}
+
+    /// ブロックがファイルに書き込まれたときに呼ばれます。
+    fn block_written(&self, _block: &BlockId) {}
+
+    /// ファイルの延長によって新しいブロックが確保されたときに呼ばれます。
+    fn block_appended(&self, _block: &BlockId) {}
+}
+
+/// 何もしない DbObserver（デフォルトで使われます）
+pub struct NoopObserver;
+
+impl DbObserver for NoopObserver {}
@@ -1,8 +1,14 @@
+use std::sync::Arc;
+use crate::storage::decimal::Decimal;
+use crate::storage::string_pool::StringPool;
+
 pub struct Page {
     // バッファは外部から直接アクセスできないよう private にしておく
     bytebuffer: Vec<u8>,
     // 現在の読み書き位置（バッファ内のインデックス）
     pos: usize,
+    // 作成時に指定された容量（バイト単位）
+    capacity: usize,
 }
 
 impl Page {
@@ -11,41 +17,86 @@ impl Page {
         Page {
             bytebuffer: Vec::with_capacity(capacity),
             pos: 0,
+            capacity,
         }
     }
 
     /// i32 の値を 4 バイト（ビッグエンディアン形式）に変換して書き込みます。
-    pub fn write_int(&mut self, value: i32) {
-        let bytes = value.to_be_bytes();
-        self.write_bytes(&bytes);
+    /// 残り容量に収まらない場合は何も書き込まずにエラーを返します（以下の write_* も同様です）。
+    pub fn write_int(&mut self, value: i32) -> std::io::Result<()> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    /// i64 の値を 8 バイト（ビッグエンディアン形式）に変換して書き込みます。
+    pub fn write_long(&mut self, value: i64) -> std::io::Result<()> {
+        self.write_bytes(&value.to_be_bytes())
+    }
+
+    /// Decimal を、スケール前の値（i64, 8 バイト）と scale（1 バイト）の 9 バイトで書き込みます。
+    pub fn write_decimal(&mut self, value: Decimal) -> std::io::Result<()> {
+        self.check_fits(9)?;
+        self.put(&value.unscaled().to_be_bytes());
+        self.put(&[value.scale()]);
+        Ok(())
     }
 
     /// 1 バイトを書き込みます。
-    pub fn write_byte(&mut self, value: u8) {
-        if self.pos < self.bytebuffer.len() {
-            // すでに存在する位置なら上書き
-            self.bytebuffer[self.pos] = value;
-        } else {
-            // それ以外は末尾に追加
-            self.bytebuffer.push(value);
-        }
-        self.pos += 1;
+    pub fn write_byte(&mut self, value: u8) -> std::io::Result<()> {
+        self.write_bytes(&[value])
     }
 
     /// &str を書き込みます。  
     /// まず文字列のバイト数（i32）を書き、続いて UTF-8 のバイト列を書き込みます。
-    pub fn write_str(&mut self, value: &str) {
+    /// 長さと本体（4 + バイト数）が残り容量に収まらない場合は何も書き込まずにエラーを返します。
+    pub fn write_str(&mut self, value: &str) -> std::io::Result<()> {
         let bytes = value.as_bytes();
-        let len = bytes.len() as i32;
-        self.write_int(len);
-        self.write_bytes(bytes);
+        self.check_fits(4 + bytes.len())?;
+        self.put(&(bytes.len() as i32).to_be_bytes());
+        self.put(bytes);
+        Ok(())
     }
 
     /// 与えられたバイト列を順次書き込みます。
-    pub fn write_bytes(&mut self, bytes: &[u8]) {
-        for &b in bytes {
-            self.write_byte(b);
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.check_fits(bytes.len())?;
+        self.put(bytes);
+        Ok(())
+    }
+
+    /// offset から len バイトの領域を取り除き、後続のバイト列を左に詰めます。
+    /// ページの長さは変わらず、詰めたことで空いた末尾の len バイトは 0 で埋めます。
+    /// 読み書き位置が取り除いた領域より後ろにあれば len だけ前に移動し、
+    /// 領域の内側にあれば offset に移動します。
+    /// 範囲がバッファの外にはみ出す場合は何もせずにエラーを返します。
+    pub fn compact(&mut self, offset: usize, len: usize) -> std::io::Result<()> {
+        let end = match offset.checked_add(len) {
+            Some(end) if end <= self.bytebuffer.len() => end,
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "range {}..{} is out of bounds for a page of {} bytes",
+                        offset,
+                        offset.saturating_add(len),
+                        self.bytebuffer.len()
+                    ),
+                ));
+            }
+        };
+        let size = self.bytebuffer.len();
+        self.bytebuffer.copy_within(end.., offset);
+        self.bytebuffer[size - len..].fill(0);
+        if self.pos >= end {
+            self.pos -= len;
+        } else if self.pos > offset {
+            self.pos = offset;
         }
+        Ok(())
+    }
+
+    /// 現在の位置から容量の末尾までの残りバイト数を返します。
+    pub fn remaining(&self) -> usize {
+        self.capacity.saturating_sub(self.pos)
     }
 
     /// 読み込み用に内部位置を 0 に戻します。  
@@ -57,34 +108,103 @@ impl Page {
     /// 現在の位置から 4 バイトを読み出し、i32（ビッグエンディアン）に変換して返します。
     /// 読み出しできない場合は None を返します。
     pub fn read_int(&mut self) -> Option<i32> {
-        if self.pos + 4 > self.bytebuffer.len() {
-            return None;
-        }
-        let slice = &self.bytebuffer[self.pos..self.pos + 4];
-        self.pos += 4;
-        Some(i32::from_be_bytes([slice[0], slice[1], slice[2], slice[3]]))
+        self.read_with(PageView::read_int)
+    }
+
+    /// 現在の位置から 8 バイトを読み出し、i64（ビッグエンディアン）に変換して返します。
+    /// 読み出しできない場合は None を返します。
+    pub fn read_long(&mut self) -> Option<i64> {
+        self.read_with(PageView::read_long)
+    }
+
+    /// 現在の位置から write_decimal の形式で Decimal を読み出します。
+    /// 読み出しできない場合や scale が不正な場合は、位置を変えずに None を返します。
+    pub fn read_decimal(&mut self) -> Option<Decimal> {
+        self.read_with(PageView::read_decimal)
     }
 
     /// 現在の位置から 1 バイトを読み出します。
     pub fn read_byte(&mut self) -> Option<u8> {
-        if self.pos >= self.bytebuffer.len() {
-            return None;
-        }
-        let value = self.bytebuffer[self.pos];
-        self.pos += 1;
-        Some(value)
+        self.read_with(PageView::read_byte)
     }
 
     /// 現在の位置から文字列を読み出します。  
     /// まず先頭の 4 バイトで文字列の長さ（i32）を読み、その後その長さ分のバイトを取り出して UTF-8 の文字列に変換します。
+    /// 長さが負の場合や、バッファに収まらない場合は None を返します。
     pub fn read_str(&mut self) -> Option<String> {
-        let len = self.read_int()? as usize;
-        if self.pos + len > self.bytebuffer.len() {
+        self.read_with(PageView::read_str)
+    }
+
+    /// read_str と同じ形式で文字列を読み出し、pool 内の共有された文字列を返します。
+    /// 同じ内容の文字列がすでに pool にあれば、新たなメモリ確保は行いません。
+    pub fn read_interned_str(&mut self, pool: &StringPool) -> Option<Arc<str>> {
+        self.read_with(|view| view.read_interned_str(pool))
+    }
+
+    /// start から len バイトの範囲を、読み書き位置 0 の独立した Page として取り出します。
+    /// 範囲はコピーされるため、元の Page には影響しません。
+    /// 範囲がバッファの外にはみ出す場合は None を返します。
+    pub fn slice(&self, start: usize, len: usize) -> Option<Page> {
+        let end = start.checked_add(len)?;
+        if end > self.bytebuffer.len() {
             return None;
         }
-        let slice = &self.bytebuffer[self.pos..self.pos + len];
-        self.pos += len;
-        std::str::from_utf8(slice).map(|s| s.to_string()).ok()
+        Some(Page {
+            bytebuffer: self.bytebuffer[start..end].to_vec(),
+            pos: 0,
+            capacity: len,
+        })
+    }
+
+    /// 読み出し専用のビューを作成します。
+    /// ビューは Page とは独立した読み出し位置を持つため、
+    /// 同じ Page に対して複数のビューから並行して読み出しても互いに影響しません。
+    pub fn view(&self) -> PageView<'_> {
+        PageView {
+            bytebuffer: &self.bytebuffer,
+            pos: 0,
+        }
+    }
+
+    // len バイトが残り容量に収まるかを確認します。すべての write_* はこれを経由します。
+    fn check_fits(&self, len: usize) -> std::io::Result<()> {
+        if len > self.remaining() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{} bytes do not fit in the remaining {} bytes of the page",
+                    len,
+                    self.remaining()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    // 容量の確認を済ませたバイト列を、現在の位置に書き込みます。
+    fn put(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            if self.pos < self.bytebuffer.len() {
+                // すでに存在する位置なら上書き
+                self.bytebuffer[self.pos] = b;
+            } else {
+                // それ以外は末尾に追加
+                self.bytebuffer.push(b);
+            }
+            self.pos += 1;
+        }
+    }
+
+    // 現在の読み出し位置から始まるビューで f を実行し、進んだ位置を Page に反映します。
+    // 値の解釈は PageView にまとめ、Page の読み出しメソッドはすべてこれを経由します。
+    fn read_with<'a, T>(&'a mut self, f: impl FnOnce(&mut PageView<'a>) -> T) -> T {
+        let mut view = PageView {
+            bytebuffer: &self.bytebuffer,
+            pos: self.pos,
+        };
+        let value = f(&mut view);
+        self.pos = view.pos;
+        value
     }
 
     // 外部には公開しないアクセサ
@@ -92,3 +212,270 @@ impl Page {
         &self.bytebuffer
     }
 }
+
+/// Page のバッファを借用する読み出し専用のビュー
+/// - 読み出し位置をビュー自身が保持するため、元の Page の pos は変化しません。
+pub struct PageView<'a> {
+    bytebuffer: &'a [u8],
+    // ビュー内の現在の読み出し位置
+    pos: usize,
+}
+
+impl<'a> PageView<'a> {
+    /// 現在の位置から 4 バイトを読み出し、i32（ビッグエンディアン）に変換して返します。
+    /// 読み出しできない場合は None を返します。
+    pub fn read_int(&mut self) -> Option<i32> {
+        let bytes = self.read_bytes(4)?;
+        Some(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// 現在の位置から 8 バイトを読み出し、i64（ビッグエンディアン）に変換して返します。
+    /// 読み出しできない場合は None を返します。
+    pub fn read_long(&mut self) -> Option<i64> {
+        let bytes = self.read_bytes(8)?;
+        Some(i64::from_be_bytes(bytes.try_into().ok()?))
+    }
+
+    /// 現在の位置から write_decimal の形式で Decimal を読み出します。
+    /// 読み出しできない場合や scale が不正な場合は、位置を変えずに None を返します。
+    pub fn read_decimal(&mut self) -> Option<Decimal> {
+        let start = self.pos;
+        let bytes = self.read_bytes(9)?;
+        let unscaled = i64::from_be_bytes(bytes[..8].try_into().ok()?);
+        let decimal = Decimal::new(unscaled, bytes[8]);
+        if decimal.is_none() {
+            self.pos = start;
+        }
+        decimal
+    }
+
+    /// 現在の位置から 1 バイトを読み出します。
+    pub fn read_byte(&mut self) -> Option<u8> {
+        Some(self.read_bytes(1)?[0])
+    }
+
+    /// 現在の位置から文字列を読み出します。
+    /// 形式は Page::read_str と同じです。
+    pub fn read_str(&mut self) -> Option<String> {
+        self.read_str_slice().map(|s| s.to_string())
+    }
+
+    /// 現在の位置から文字列を読み出し、pool 内の共有された文字列を返します。
+    /// 形式は Page::read_interned_str と同じです。
+    pub fn read_interned_str(&mut self, pool: &StringPool) -> Option<Arc<str>> {
+        self.read_str_slice().map(|s| pool.intern(s))
+    }
+
+    // 長さ（i32）付きの文字列を、バッファを借用したまま読み出します。
+    // 長さが負の場合や、バッファに収まらない場合は None を返します。
+    fn read_str_slice(&mut self) -> Option<&'a str> {
+        let len = usize::try_from(self.read_int()?).ok()?;
+        std::str::from_utf8(self.read_bytes(len)?).ok()
+    }
+
+    // 現在の位置から len バイトを借用して読み出し、位置を進めます。
+    // バッファに収まらない場合は位置を変えずに None を返します。
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.bytebuffer.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    /// 読み出し位置を先頭に戻します。
+    pub fn rewind(&mut self) {
+        self.pos = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::decimal::Decimal;
+    use crate::storage::page::Page;
+    use crate::storage::string_pool::StringPool;
+    use std::sync::Arc;
+
+    #[test]
+    fn views_read_independently() {
+        let mut page = Page::new(16);
+        page.write_int(42).unwrap();
+        page.write_int(7).unwrap();
+
+        let mut first = page.view();
+        let mut second = page.view();
+        assert_eq!(first.read_int(), Some(42));
+        assert_eq!(first.read_int(), Some(7));
+        assert_eq!(second.read_int(), Some(42));
+        assert_eq!(first.read_int(), None);
+    }
+
+    #[test]
+    fn negative_string_length_is_rejected() {
+        let mut page = Page::new(8);
+        page.write_int(-1).unwrap();
+        page.write_bytes(b"abcd").unwrap();
+
+        page.flip();
+        assert_eq!(page.read_str(), None);
+        assert_eq!(page.view().read_str(), None);
+        assert_eq!(page.view().read_interned_str(&StringPool::new()), None);
+    }
+
+    #[test]
+    fn write_str_rejects_oversized_string() {
+        let mut page = Page::new(12);
+        page.write_str("abcd").unwrap();
+        assert_eq!(page.remaining(), 4);
+
+        // 長さの 4 バイトだけで残り容量を使い切るため、1 文字でも書き込めません。
+        assert!(page.write_str("e").is_err());
+        assert_eq!(page.remaining(), 4);
+
+        page.flip();
+        assert_eq!(page.read_str(), Some("abcd".to_string()));
+        assert_eq!(page.read_int(), None);
+    }
+
+    #[test]
+    fn empty_string_round_trips() {
+        let mut page = Page::new(12);
+        page.write_str("").unwrap();
+        page.write_str("x").unwrap();
+        // 空文字列は長さ 0 の 4 バイトだけを書き込みます。
+        assert_eq!(page.remaining(), 3);
+
+        page.flip();
+        assert_eq!(page.read_str(), Some(String::new()));
+        assert_eq!(page.read_str(), Some("x".to_string()));
+
+        let mut view = page.view();
+        assert_eq!(view.read_int(), Some(0));
+        view.rewind();
+        assert_eq!(view.read_str(), Some(String::new()));
+    }
+
+    #[test]
+    fn compact_shifts_tail_left() {
+        let mut page = Page::new(12);
+        page.write_int(1).unwrap();
+        page.write_int(2).unwrap();
+        page.write_int(3).unwrap();
+
+        page.compact(4, 4).unwrap();
+        // ページの長さは変わらず、空いた末尾は 0 で埋められます。
+        assert_eq!(page.bytebuffer(), &vec![0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 0]);
+        // 書き込み位置も取り除いた分だけ前に移動します。
+        assert_eq!(page.remaining(), 4);
+
+        page.flip();
+        assert_eq!(page.read_int(), Some(1));
+        assert_eq!(page.read_int(), Some(3));
+        assert_eq!(page.read_int(), Some(0));
+
+        assert!(page.compact(10, 4).is_err());
+        assert_eq!(page.bytebuffer().len(), 12);
+    }
+
+    #[test]
+    fn long_round_trips_beyond_i32_range() {
+        let mut page = Page::new(24);
+        page.write_long(i64::from(i32::MAX) + 1).unwrap();
+        page.write_long(i64::MIN).unwrap();
+        page.write_int(5).unwrap();
+
+        page.flip();
+        assert_eq!(page.read_long(), Some(2_147_483_648));
+        assert_eq!(page.read_long(), Some(i64::MIN));
+        assert_eq!(page.read_long(), None);
+        assert_eq!(page.read_int(), Some(5));
+
+        let mut view = page.view();
+        assert_eq!(view.read_long(), Some(2_147_483_648));
+    }
+
+    #[test]
+    fn interned_strings_are_shared() {
+        let pool = StringPool::new();
+        let mut page = Page::new(64);
+        page.write_str("student").unwrap();
+        page.write_str("student").unwrap();
+        page.write_str("dept").unwrap();
+
+        page.flip();
+        let first = page.read_interned_str(&pool).unwrap();
+        let second = page.read_interned_str(&pool).unwrap();
+        let third = page.read_interned_str(&pool).unwrap();
+        assert_eq!(&*first, "student");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(&*third, "dept");
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn decimal_round_trips_exactly() {
+        let mut page = Page::new(18);
+        page.write_decimal(Decimal::new(1234, 2).unwrap()).unwrap();
+        page.write_decimal(Decimal::new(-5, 3).unwrap()).unwrap();
+
+        page.flip();
+        let value = page.read_decimal().unwrap();
+        assert_eq!(value.unscaled(), 1234);
+        assert_eq!(value.scale(), 2);
+        assert_eq!(value.to_string(), "12.34");
+        assert_eq!(page.read_decimal().unwrap().to_string(), "-0.005");
+        assert_eq!(page.read_decimal(), None);
+
+        // scale が不正な値は読み出さず、位置も進めない
+        let mut page = Page::new(9);
+        page.write_long(1).unwrap();
+        page.write_byte(Decimal::MAX_SCALE + 1).unwrap();
+        page.flip();
+        assert_eq!(page.read_decimal(), None);
+        assert_eq!(page.remaining(), 9);
+    }
+
+    #[test]
+    fn decimals_compare_across_scales() {
+        let a = Decimal::new(1234, 2).unwrap(); // 12.34
+        let b = Decimal::new(12340, 3).unwrap(); // 12.340
+        let c = Decimal::new(12341, 3).unwrap(); // 12.341
+        assert_eq!(a, b);
+        assert!(a < c);
+        assert!(Decimal::new(-1, 0).unwrap() < Decimal::new(-999, 3).unwrap());
+        assert!(Decimal::new(1, Decimal::MAX_SCALE + 1).is_none());
+    }
+
+    #[test]
+    fn slice_extracts_standalone_record() {
+        // (int, str) のレコードを 2 つ並べたページ
+        let mut page = Page::new(64);
+        page.write_int(1).unwrap();
+        page.write_str("first").unwrap();
+        let second_start = 4 + 4 + 5;
+        page.write_int(2).unwrap();
+        page.write_str("second").unwrap();
+        let second_len = 4 + 4 + 6;
+
+        let mut record = page.slice(second_start, second_len).unwrap();
+        assert_eq!(record.read_int(), Some(2));
+        assert_eq!(record.read_str(), Some("second".to_string()));
+        assert_eq!(record.read_byte(), None);
+
+        assert!(page.slice(second_start, second_len + 1).is_none());
+        assert!(page.slice(usize::MAX, 2).is_none());
+    }
+
+    #[test]
+    fn writes_past_capacity_are_rejected() {
+        let mut page = Page::new(6);
+        page.write_int(1).unwrap();
+        assert!(page.write_int(2).is_err());
+        assert!(page.write_long(3).is_err());
+        assert!(page.write_decimal(Decimal::new(4, 0).unwrap()).is_err());
+        assert!(page.write_bytes(&[5, 6, 7]).is_err());
+        page.write_byte(8).unwrap();
+        page.write_byte(9).unwrap();
+        assert!(page.write_byte(10).is_err());
+        assert_eq!(page.bytebuffer(), &vec![0, 0, 0, 1, 8, 9]);
+    }
+}
@@ -0,0 +1,32 @@
+use std::path::{Path, PathBuf};
+
+/// FileManager がファイル名から実際のファイルパスを決定するための戦略
+pub trait PathStrategy: Send + Sync {
+    /// db_directory と filename から、ファイルのフルパスを返します。
+    fn resolve(&self, db_directory: &Path, filename: &Path) -> PathBuf;
+}
+
+/// すべてのファイルを db_directory の直下に置く戦略（デフォルト）
+/// - 例: `db/student.tbl`
+pub struct FlatPathStrategy;
+
+impl PathStrategy for FlatPathStrategy {
+    fn resolve(&self, db_directory: &Path, filename: &Path) -> PathBuf {
+        db_directory.join(filename)
+    }
+}
+
+/// ファイルごとに、拡張子を除いた名前のサブディレクトリを作って置く戦略
+/// - 例: `db/student/student.tbl`
+/// - サブディレクトリを別のファイルシステムへのシンボリックリンクにすることで、
+///   テーブルを複数のディスクに分散できます。
+pub struct ShardedPathStrategy;
+
+impl PathStrategy for ShardedPathStrategy {
+    fn resolve(&self, db_directory: &Path, filename: &Path) -> PathBuf {
+        match filename.file_stem() {
+            Some(stem) => db_directory.join(stem).join(filename),
+            None => db_directory.join(filename),
+        }
+    }
+}
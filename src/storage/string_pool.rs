@@ -0,0 +1,47 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// 同じ内容の文字列を共有するための文字列プール
+/// - カタログのテーブル名やフィールド名のように、同じ文字列を何度も読み出す場合に、
+///   読み出しのたびに String を確保する代わりに同じ Arc<str> を返します。
+/// - 複数のスレッドから共有できるよう、内部に Mutex を保持します。
+pub struct StringPool {
+    strings: Mutex<HashSet<Arc<str>>>,
+}
+
+impl StringPool {
+    /// 空の StringPool を作成します。
+    pub fn new() -> StringPool {
+        StringPool {
+            strings: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// value と同じ内容の文字列をプールから返します。
+    /// プールになければ新たに確保して登録します。
+    pub fn intern(&self, value: &str) -> Arc<str> {
+        let mut strings = self.strings.lock().unwrap();
+        if let Some(s) = strings.get(value) {
+            return s.clone();
+        }
+        let s: Arc<str> = Arc::from(value);
+        strings.insert(s.clone());
+        s
+    }
+
+    /// プールに登録されている文字列の数を返します。
+    pub fn len(&self) -> usize {
+        self.strings.lock().unwrap().len()
+    }
+
+    /// プールが空かどうかを返します。
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for StringPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}